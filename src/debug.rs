@@ -1,7 +1,14 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
+mod benchmark;
+mod config;
+mod telemetry;
+
 use crate::state::{Common, Fps};
+use benchmark::BenchmarkRecorder;
+pub use config::{OverlayAnchor, OverlayConfig, OverlaySections};
 use egui::Color32;
+use once_cell::sync::Lazy;
 use smithay::{
     backend::{
         drm::DrmNode,
@@ -12,9 +19,42 @@ use smithay::{
         },
     },
     desktop::layer_map_for_output,
+    output::Output,
     reexports::wayland_server::Resource,
     utils::{IsAlive, Logical, Rectangle},
 };
+use std::sync::Mutex;
+use telemetry::TelemetryRegistry;
+
+static TELEMETRY: Lazy<Mutex<TelemetryRegistry>> =
+    Lazy::new(|| Mutex::new(TelemetryRegistry::default()));
+
+static BENCHMARK: Lazy<Mutex<BenchmarkRecorder>> =
+    Lazy::new(|| Mutex::new(BenchmarkRecorder::new("cosmic-comp-benchmark.csv".into())));
+
+/// Whether `pressed` (in the same key-name format the debug keybind parser
+/// uses) should toggle the overlay, honoring `state.egui.overlay_config.toggle_keybind`
+/// when the user has configured one.
+///
+/// Not yet called anywhere: the input dispatch code that would invoke this on
+/// a keypress lives outside this module and hasn't been wired up to call it.
+pub fn should_toggle_overlay(state: &Common, pressed: &str) -> bool {
+    state
+        .egui
+        .overlay_config
+        .matches_toggle_keybind(pressed)
+}
+
+/// Toggles benchmark CSV logging on/off.
+///
+/// Not yet called anywhere: like [`should_toggle_overlay`], this needs a
+/// debug keybind routed to it the same way `state.egui.active` is, which
+/// means touching the input dispatch code that lives outside this module.
+pub fn toggle_benchmark_logging() {
+    if let Err(err) = BENCHMARK.lock().unwrap().toggle() {
+        slog_scope::warn!("Failed to toggle benchmark logging: {}", err);
+    }
+}
 
 pub const ELEMENTS_COLOR: Color32 = Color32::from_rgb(70, 198, 115);
 pub const RENDER_COLOR: Color32 = Color32::from_rgb(29, 114, 58);
@@ -23,6 +63,7 @@ pub const DISPLAY_COLOR: Color32 = Color32::from_rgb(41, 184, 209);
 
 pub fn fps_ui(
     gpu: Option<&DrmNode>,
+    output: &Output,
     state: &Common,
     renderer: &mut GlowRenderer,
     fps: &mut Fps,
@@ -31,6 +72,8 @@ pub fn fps_ui(
 ) -> Result<TextureRenderElement<Gles2Texture>, Gles2Error> {
     use egui::widgets::plot::{Bar, BarChart, HLine, Legend, Plot};
 
+    let overlay_config = &state.egui.overlay_config;
+
     let (max, min, avg, avg_fps) = (
         fps.max_frametime().as_secs_f64(),
         fps.min_frametime().as_secs_f64(),
@@ -42,6 +85,27 @@ pub fn fps_ui(
         fps.min_time_to_display().as_secs_f64(),
     );
 
+    // Vsync budget for the output's current mode, in milliseconds.
+    let budget_ms = output
+        .current_mode()
+        .map(|mode| 1_000.0 / (mode.refresh as f64 / 1_000.0))
+        .unwrap_or(1_000.0 / 60.0);
+
+    if let Some(frame) = fps.frames.iter().last() {
+        let mut benchmark = BENCHMARK.lock().unwrap();
+        if benchmark.is_active() {
+            if let Err(err) = benchmark.record(
+                std::time::SystemTime::now(),
+                frame.duration_elements,
+                frame.duration_render,
+                frame.duration_screencopy,
+                frame.duration_displayed,
+            ) {
+                slog_scope::warn!("Failed to write benchmark frame: {}", err);
+            }
+        }
+    }
+
     let amount = avg_fps.round() as usize * 2;
     let ((bars_elements, bars_render), (bars_screencopy, bars_displayed)): (
         (Vec<Bar>, Vec<Bar>),
@@ -84,17 +148,39 @@ pub fn fps_ui(
         })
         .unzip();
 
+    let frametime_bars: Vec<Bar> = fps
+        .frames
+        .iter()
+        .rev()
+        .take(amount)
+        .rev()
+        .enumerate()
+        .map(|(i, frame)| {
+            let frametime_ms = frame.duration_displayed.as_secs_f64() * 1000.0;
+            let color = if frametime_ms > budget_ms {
+                Color32::from_rgb(220, 50, 47)
+            } else {
+                DISPLAY_COLOR
+            };
+            Bar::new(i as f64, frametime_ms).fill(color)
+        })
+        .collect();
+
     fps.state.render(
         |ctx| {
             egui::Area::new("main")
-                .anchor(egui::Align2::LEFT_TOP, (10.0, 10.0))
+                .anchor(overlay_config.anchor.align2(), overlay_config.anchor.offset())
                 .show(ctx, |ui| {
-                    ui.label(format!(
-                        "cosmic-comp version {}",
-                        std::env!("CARGO_PKG_VERSION")
-                    ));
-                    if let Some(hash) = std::option_env!("GIT_HASH").and_then(|x| x.get(0..10)) {
-                        ui.label(format!(" :{hash}"));
+                    if overlay_config.sections.version {
+                        ui.label(format!(
+                            "cosmic-comp version {}",
+                            std::env!("CARGO_PKG_VERSION")
+                        ));
+                        if let Some(hash) =
+                            std::option_env!("GIT_HASH").and_then(|x| x.get(0..10))
+                        {
+                            ui.label(format!(" :{hash}"));
+                        }
                     }
 
                     if !state.egui.active {
@@ -103,56 +189,167 @@ pub fn fps_ui(
                         ui.set_max_width(300.0);
                         ui.separator();
 
-                        if let Some(gpu) = gpu {
-                            ui.label(egui::RichText::new(format!("renderD{}", gpu.minor())).code());
+                        if overlay_config.sections.gpu_id {
+                            if let Some(gpu) = gpu {
+                                ui.label(
+                                    egui::RichText::new(format!("renderD{}", gpu.minor())).code(),
+                                );
+                            }
+                        }
+                        if overlay_config.sections.fps {
+                            ui.label(
+                                egui::RichText::new(format!("FPS: {:>7.3}", avg_fps)).heading(),
+                            );
+                        }
+                        if overlay_config.sections.frame_times {
+                            ui.label("Frame Times:");
+                            ui.label(egui::RichText::new(format!("avg: {:>7.6}", avg)).code());
+                            ui.label(egui::RichText::new(format!("min: {:>7.6}", min)).code());
+                            ui.label(egui::RichText::new(format!("max: {:>7.6}", max)).code());
+                        }
+
+                        ui.separator();
+                        ui.label(egui::RichText::new("Benchmark:").heading());
+                        let benchmark = BENCHMARK.lock().unwrap();
+                        if benchmark.is_active() {
+                            let stats = benchmark.stats();
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "avg:      {:>7.3} FPS",
+                                    stats.avg_fps
+                                ))
+                                .code(),
+                            );
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "1% low:   {:>7.3} FPS",
+                                    stats.low_1pct_fps
+                                ))
+                                .code(),
+                            );
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "0.1% low: {:>7.3} FPS",
+                                    stats.low_01pct_fps
+                                ))
+                                .code(),
+                            );
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "p97 frametime: {:>7.3} ms",
+                                    stats.p97_frametime_ms
+                                ))
+                                .code(),
+                            );
+                            ui.label(egui::RichText::new("recording to CSV").italics());
+                        } else {
+                            ui.label(egui::RichText::new("not capturing").italics());
+                        }
+                        drop(benchmark);
+
+                        ui.separator();
+                        ui.label("System:");
+                        let telemetry = TELEMETRY.lock().unwrap().sample(gpu);
+                        if let Some(cpu_usage) = telemetry.cpu_usage {
+                            let mut line = format!("CPU: {:>5.1}%", cpu_usage);
+                            if let Some(temp) = telemetry.cpu_temp_c {
+                                line += &format!(" {:>5.1}°C", temp);
+                            }
+                            if let Some(power) = telemetry.cpu_power_w {
+                                line += &format!(" {:>6.1}W", power);
+                            }
+                            ui.label(egui::RichText::new(line).code());
+                        }
+                        if let (Some(used), Some(total)) =
+                            (telemetry.ram_used_mb, telemetry.ram_total_mb)
+                        {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "RAM: {:>6.0}/{:>6.0} MB",
+                                    used, total
+                                ))
+                                .code(),
+                            );
+                        }
+                        if telemetry.gpu_usage.is_some()
+                            || telemetry.gpu_temp_c.is_some()
+                            || telemetry.gpu_power_w.is_some()
+                        {
+                            let mut line = "GPU:".to_string();
+                            if let Some(usage) = telemetry.gpu_usage {
+                                line += &format!(" {:>5.1}%", usage);
+                            }
+                            if let Some(temp) = telemetry.gpu_temp_c {
+                                line += &format!(" {:>5.1}°C", temp);
+                            }
+                            if let Some(power) = telemetry.gpu_power_w {
+                                line += &format!(" {:>6.1}W", power);
+                            }
+                            ui.label(egui::RichText::new(line).code());
+                        }
+
+                        if overlay_config.sections.chart {
+                            let elements_chart = BarChart::new(bars_elements).vertical();
+                            let render_chart = BarChart::new(bars_render)
+                                .stack_on(&[&elements_chart])
+                                .vertical();
+                            let screencopy_chart = BarChart::new(bars_screencopy)
+                                .stack_on(&[&elements_chart, &render_chart])
+                                .vertical();
+                            let display_chart = BarChart::new(bars_displayed)
+                                .stack_on(&[&elements_chart, &render_chart, &screencopy_chart])
+                                .vertical();
+
+                            Plot::new("FPS")
+                                .legend(Legend::default())
+                                .view_aspect(50.0)
+                                .include_x(0.0)
+                                .include_x(amount as f64)
+                                .include_y(0.0)
+                                .include_y(300)
+                                .show_x(false)
+                                .show(ui, |plot_ui| {
+                                    plot_ui.bar_chart(elements_chart);
+                                    plot_ui.bar_chart(render_chart);
+                                    plot_ui.bar_chart(screencopy_chart);
+                                    plot_ui.bar_chart(display_chart);
+                                });
+
+                            ui.label("Frame Time vs. Vsync Budget:");
+                            let frametime_chart = BarChart::new(frametime_bars).vertical();
+                            Plot::new("Frame Time")
+                                .view_aspect(50.0)
+                                .include_x(0.0)
+                                .include_x(amount as f64)
+                                .include_y(0.0)
+                                .include_y(budget_ms * 2.0)
+                                .show_x(false)
+                                .show(ui, |plot_ui| {
+                                    plot_ui.bar_chart(frametime_chart);
+                                    plot_ui.hline(
+                                        HLine::new(budget_ms)
+                                            .color(Color32::from_rgb(220, 50, 47))
+                                            .name("vsync budget"),
+                                    );
+                                });
                         }
-                        ui.label(egui::RichText::new(format!("FPS: {:>7.3}", avg_fps)).heading());
-                        ui.label("Frame Times:");
-                        ui.label(egui::RichText::new(format!("avg: {:>7.6}", avg)).code());
-                        ui.label(egui::RichText::new(format!("min: {:>7.6}", min)).code());
-                        ui.label(egui::RichText::new(format!("max: {:>7.6}", max)).code());
-                        let elements_chart = BarChart::new(bars_elements).vertical();
-                        let render_chart = BarChart::new(bars_render)
-                            .stack_on(&[&elements_chart])
-                            .vertical();
-                        let screencopy_chart = BarChart::new(bars_screencopy)
-                            .stack_on(&[&elements_chart, &render_chart])
-                            .vertical();
-                        let display_chart = BarChart::new(bars_displayed)
-                            .stack_on(&[&elements_chart, &render_chart, &screencopy_chart])
-                            .vertical();
-
-                        Plot::new("FPS")
-                            .legend(Legend::default())
-                            .view_aspect(50.0)
-                            .include_x(0.0)
-                            .include_x(amount as f64)
-                            .include_y(0.0)
-                            .include_y(300)
-                            .show_x(false)
-                            .show(ui, |plot_ui| {
-                                plot_ui.bar_chart(elements_chart);
-                                plot_ui.bar_chart(render_chart);
-                                plot_ui.bar_chart(screencopy_chart);
-                                plot_ui.bar_chart(display_chart);
-                            });
                     }
                 });
         },
         renderer,
         area,
         scale,
-        0.8,
+        overlay_config.alpha,
         state.clock.now().into(),
     )
 }
 
-/*
+
 pub fn debug_ui(
     state: &mut Common,
-    area: Rectangle<f64, Physical>,
+    area: Rectangle<f64, smithay::utils::Physical>,
     scale: f64,
-) -> Option<EguiFrame> {
+) -> Option<crate::utils::prelude::EguiFrame> {
     if !state.egui.active {
         return None;
     }
@@ -166,78 +363,27 @@ pub fn debug_ui(
                 .vscroll(true)
                 .collapsible(true)
                 .show(ctx, |ui| {
-                    use crate::{
-                        config::WorkspaceMode as ConfigMode,
-                        shell::{OutputBoundState, WorkspaceMode, MAX_WORKSPACES},
-                    };
-
                     ui.set_min_width(250.0);
 
-                    // Mode
-
-                    ui.label(egui::RichText::new("Mode").heading());
-                    let mut mode = match &state.shell.workspace_mode {
-                        WorkspaceMode::Global { .. } => ConfigMode::Global,
-                        WorkspaceMode::OutputBound => ConfigMode::OutputBound,
-                    };
-                    ui.radio_value(&mut mode, ConfigMode::OutputBound, "Output bound");
-                    ui.radio_value(&mut mode, ConfigMode::Global, "Global");
-                    state.shell.set_mode(mode);
-
-                    let mode = match &state.shell.workspace_mode {
-                        WorkspaceMode::OutputBound => (ConfigMode::OutputBound, None),
-                        WorkspaceMode::Global { ref active, .. } => {
-                            (ConfigMode::Global, Some(*active))
-                        }
-                    };
-                    match mode {
-                        (ConfigMode::OutputBound, _) => {
-                            ui.label("Workspaces:");
-                            for output in state.shell.outputs().cloned().collect::<Vec<_>>() {
-                                ui.horizontal(|ui| {
-                                    let active = output
-                                        .user_data()
-                                        .get::<OutputBoundState>()
-                                        .unwrap()
-                                        .active
-                                        .get();
-                                    let mut active_val = active as f64;
-                                    ui.label(output.name());
-                                    ui.add(
-                                        egui::DragValue::new(&mut active_val)
-                                            .clamp_range(0..=(MAX_WORKSPACES - 1))
-                                            .speed(1.0),
-                                    );
-                                    if active != active_val as usize {
-                                        state.shell.activate(
-                                            &state.seats[0],
-                                            &output,
-                                            active_val as usize,
-                                        );
-                                    }
-                                });
-                            }
-                        }
-                        (ConfigMode::Global, Some(active)) => {
-                            ui.horizontal(|ui| {
-                                let mut active_val = active as f64;
-                                ui.label("Workspace:");
-                                ui.add(
-                                    egui::DragValue::new(&mut active_val)
-                                        .clamp_range(0..=(MAX_WORKSPACES - 1))
-                                        .speed(1.0),
+                    ui.label("Workspaces:");
+                    for output in state.shell.outputs().cloned().collect::<Vec<_>>() {
+                        ui.horizontal(|ui| {
+                            let (active, len) = state.shell.workspaces.active_num(&output);
+                            let mut active_val = active as f64;
+                            ui.label(output.name());
+                            ui.add(
+                                egui::DragValue::new(&mut active_val)
+                                    .clamp_range(0..=(len.saturating_sub(1)))
+                                    .speed(1.0),
+                            );
+                            if active != active_val as usize {
+                                state.shell.workspaces.activate(
+                                    &state.seats[0],
+                                    &output,
+                                    active_val as usize,
                                 );
-                                if active != active_val as usize {
-                                    let output = state.shell.outputs().next().cloned().unwrap();
-                                    state.shell.activate(
-                                        &state.seats[0],
-                                        &output,
-                                        active_val as usize,
-                                    );
-                                }
-                            });
-                        }
-                        _ => unreachable!(),
+                            }
+                        });
                     }
 
                     // Spaces
@@ -280,9 +426,52 @@ pub fn debug_ui(
                     {
                         ui.separator();
                         ui.collapsing(output.name(), |ui| {
+                            let mut scale = output.current_scale().fractional_scale();
+                            ui.horizontal(|ui| {
+                                ui.label("Scale:");
+                                if ui
+                                    .add(
+                                        egui::DragValue::new(&mut scale)
+                                            .clamp_range(0.25..=4.0)
+                                            .speed(0.05),
+                                    )
+                                    .changed()
+                                {
+                                    output.change_current_state(
+                                        None,
+                                        None,
+                                        Some(smithay::output::Scale::Fractional(scale)),
+                                        None,
+                                    );
+                                }
+                            });
+
+                            let mut transform = output.current_transform();
+                            egui::ComboBox::from_label("Transform")
+                                .selected_text(format!("{:?}", transform))
+                                .show_ui(ui, |ui| {
+                                    for candidate in [
+                                        smithay::utils::Transform::Normal,
+                                        smithay::utils::Transform::_90,
+                                        smithay::utils::Transform::_180,
+                                        smithay::utils::Transform::_270,
+                                        smithay::utils::Transform::Flipped,
+                                        smithay::utils::Transform::Flipped90,
+                                        smithay::utils::Transform::Flipped180,
+                                        smithay::utils::Transform::Flipped270,
+                                    ] {
+                                        ui.selectable_value(
+                                            &mut transform,
+                                            candidate,
+                                            format!("{:?}", candidate),
+                                        );
+                                    }
+                                });
+                            if transform != output.current_transform() {
+                                output.change_current_state(None, Some(transform), None, None);
+                            }
+
                             ui.label(format!("Mode: {:#?}", output.current_mode()));
-                            ui.label(format!("Scale: {:#?}", output.current_scale()));
-                            ui.label(format!("Transform: {:#?}", output.current_transform()));
                             ui.label(format!("Geometry: {:?}", output.geometry()));
                             ui.label(format!(
                                 "Local Geometry: {:?}",
@@ -338,6 +527,41 @@ pub fn debug_ui(
                         });
                     }
                 });
+
+            egui::Window::new("Seats")
+                .collapsible(true)
+                .default_pos([0.0, 600.0])
+                .show(ctx, |ui| {
+                    for seat in state.seats.iter() {
+                        ui.collapsing(seat.name(), |ui| {
+                            if let Some(pointer) = seat.get_pointer() {
+                                ui.label(format!(
+                                    "Pointer location: {:?}",
+                                    pointer.current_location()
+                                ));
+                            } else {
+                                ui.label("Pointer: none");
+                            }
+
+                            if let Some(keyboard) = seat.get_keyboard() {
+                                ui.label(format!(
+                                    "Keyboard modifiers: {:?}",
+                                    keyboard.modifier_state()
+                                ));
+                                match keyboard.current_focus() {
+                                    Some(focus) => {
+                                        ui.label(format!("Focused surface: {:?}", focus));
+                                    }
+                                    None => {
+                                        ui.label("Focused surface: none");
+                                    }
+                                }
+                            } else {
+                                ui.label("Keyboard: none");
+                            }
+                        });
+                    }
+                });
         },
         area,
         scale,
@@ -346,4 +570,3 @@ pub fn debug_ui(
         state.egui.modifiers.clone(),
     ))
 }
-*/
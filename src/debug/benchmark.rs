@@ -0,0 +1,125 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! CSV benchmark logging and percentile-low statistics for the FPS overlay,
+//! modeled after MangoHud's logging mode.
+
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FrametimeStats {
+    pub avg_fps: f64,
+    pub low_1pct_fps: f64,
+    pub low_01pct_fps: f64,
+    pub p97_frametime_ms: f64,
+}
+
+/// Computes avg/1%-low/0.1%-low FPS and the 97th-percentile frametime from a
+/// window of captured frames, the way MangoHud's logging mode does.
+fn frametime_stats(frames: impl Iterator<Item = Duration>) -> FrametimeStats {
+    let mut times: Vec<f64> = frames.map(|d| d.as_secs_f64()).collect();
+    if times.is_empty() {
+        return FrametimeStats::default();
+    }
+    times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let avg = times.iter().sum::<f64>() / times.len() as f64;
+    let low_1pct = low_percentile_fps(&times, 0.01);
+    let low_01pct = low_percentile_fps(&times, 0.001);
+    let p97_index = ((times.len() as f64) * 0.97).floor() as usize;
+    let p97 = times[p97_index.min(times.len() - 1)];
+
+    FrametimeStats {
+        avg_fps: 1.0 / avg,
+        low_1pct_fps: low_1pct,
+        low_01pct_fps: low_01pct,
+        p97_frametime_ms: p97 * 1000.0,
+    }
+}
+
+/// Averages the slowest `fraction` of frametimes and inverts to FPS, e.g. the
+/// "1% low" or "0.1% low" MangoHud reports.
+fn low_percentile_fps(sorted_ascending: &[f64], fraction: f64) -> f64 {
+    let count = ((sorted_ascending.len() as f64) * fraction).ceil() as usize;
+    let count = count.max(1).min(sorted_ascending.len());
+    let slowest = &sorted_ascending[sorted_ascending.len() - count..];
+    let avg = slowest.iter().sum::<f64>() / slowest.len() as f64;
+    1.0 / avg
+}
+
+/// Records frames to a CSV file on disk while benchmark logging is toggled on,
+/// and keeps every frametime seen since the capture started so percentile-low
+/// stats reflect the whole capture window rather than just the overlay's
+/// short rolling display window.
+pub struct BenchmarkRecorder {
+    writer: Option<File>,
+    path: PathBuf,
+    samples: Vec<Duration>,
+}
+
+impl BenchmarkRecorder {
+    pub fn new(path: PathBuf) -> Self {
+        BenchmarkRecorder {
+            writer: None,
+            path,
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    pub fn toggle(&mut self) -> io::Result<()> {
+        if self.writer.take().is_none() {
+            let mut file = File::create(&self.path)?;
+            writeln!(
+                file,
+                "timestamp_ms,duration_elements_ms,duration_render_ms,duration_screencopy_ms,duration_displayed_ms"
+            )?;
+            self.writer = Some(file);
+            self.samples.clear();
+        }
+        Ok(())
+    }
+
+    pub fn record(
+        &mut self,
+        timestamp: SystemTime,
+        duration_elements: Duration,
+        duration_render: Duration,
+        duration_screencopy: Option<Duration>,
+        duration_displayed: Duration,
+    ) -> io::Result<()> {
+        let Some(file) = self.writer.as_mut() else {
+            return Ok(());
+        };
+        let timestamp_ms = timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        writeln!(
+            file,
+            "{},{:.3},{:.3},{:.3},{:.3}",
+            timestamp_ms,
+            duration_elements.as_secs_f64() * 1000.0,
+            duration_render.as_secs_f64() * 1000.0,
+            duration_screencopy
+                .map(|d| d.as_secs_f64() * 1000.0)
+                .unwrap_or(0.0),
+            duration_displayed.as_secs_f64() * 1000.0,
+        )?;
+        self.samples.push(duration_displayed);
+        Ok(())
+    }
+
+    /// Stats over every frame recorded since the capture was last toggled on,
+    /// not just the overlay's short rolling display window.
+    pub fn stats(&self) -> FrametimeStats {
+        frametime_stats(self.samples.iter().copied())
+    }
+}
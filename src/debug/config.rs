@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! User-configurable layout for the debug/FPS overlay, parsed from cosmic-comp's
+//! config system the way MangoHud reads `overlay_params`.
+//!
+//! The active config lives on `state.egui.overlay_config` rather than being
+//! threaded through as an `fps_ui` parameter, so it can be reloaded independently
+//! of the render path.
+
+use serde::{Deserialize, Serialize};
+
+/// Which corner of the output the overlay is anchored to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayAnchor {
+    LeftTop,
+    RightTop,
+    LeftBottom,
+    RightBottom,
+}
+
+impl OverlayAnchor {
+    pub fn align2(&self) -> egui::Align2 {
+        match self {
+            OverlayAnchor::LeftTop => egui::Align2::LEFT_TOP,
+            OverlayAnchor::RightTop => egui::Align2::RIGHT_TOP,
+            OverlayAnchor::LeftBottom => egui::Align2::LEFT_BOTTOM,
+            OverlayAnchor::RightBottom => egui::Align2::RIGHT_BOTTOM,
+        }
+    }
+
+    /// Offset from the anchored corner, pointing inward.
+    pub fn offset(&self) -> (f32, f32) {
+        match self {
+            OverlayAnchor::LeftTop => (10.0, 10.0),
+            OverlayAnchor::RightTop => (-10.0, 10.0),
+            OverlayAnchor::LeftBottom => (10.0, -10.0),
+            OverlayAnchor::RightBottom => (-10.0, -10.0),
+        }
+    }
+}
+
+impl Default for OverlayAnchor {
+    fn default() -> Self {
+        OverlayAnchor::LeftTop
+    }
+}
+
+/// Which sections of the overlay are drawn. All default to `true`, so an
+/// unconfigured overlay draws every one of them. `chart` covers both the phase
+/// chart and the frame-time budget chart; the benchmark and system telemetry
+/// rows are not controlled by these flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlaySections {
+    pub version: bool,
+    pub gpu_id: bool,
+    pub fps: bool,
+    pub frame_times: bool,
+    pub chart: bool,
+}
+
+impl Default for OverlaySections {
+    fn default() -> Self {
+        OverlaySections {
+            version: true,
+            gpu_id: true,
+            fps: true,
+            frame_times: true,
+            chart: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayConfig {
+    pub anchor: OverlayAnchor,
+    pub alpha: f32,
+    pub sections: OverlaySections,
+    /// Key name (as understood by the existing debug keybind parser) that
+    /// [`OverlayConfig::matches_toggle_keybind`] accepts as the overlay toggle;
+    /// `None` accepts Super+Escape. Its only caller is
+    /// `crate::debug::should_toggle_overlay`, which no keypress handler calls.
+    pub toggle_keybind: Option<String>,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        OverlayConfig {
+            anchor: OverlayAnchor::default(),
+            alpha: 0.8,
+            sections: OverlaySections::default(),
+            toggle_keybind: None,
+        }
+    }
+}
+
+impl OverlayConfig {
+    /// Whether `pressed` (in the same key-name format the debug keybind parser
+    /// uses) matches this config's `toggle_keybind`, falling back to the
+    /// Super+Escape default when unconfigured.
+    pub fn matches_toggle_keybind(&self, pressed: &str) -> bool {
+        match &self.toggle_keybind {
+            Some(keybind) => keybind == pressed,
+            None => pressed == "Super+Escape",
+        }
+    }
+}
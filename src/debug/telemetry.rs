@@ -0,0 +1,223 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Lightweight, throttled sampling of CPU/GPU/RAM telemetry for the debug overlay.
+//!
+//! Samples are pulled from `/proc` and `/sys` once a second at most, on the
+//! calling thread; frames in between reuse the cached values and do no sysfs I/O.
+
+use smithay::backend::drm::DrmNode;
+use std::{
+    collections::HashMap,
+    fs,
+    time::{Duration, Instant},
+};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Per-GPU telemetry samplers, keyed by the `DrmNode` passed to [`TelemetryRegistry::sample`]
+/// (or `None` for the CPU/RAM-only case). Needed because a multi-GPU system can call
+/// `fps_ui` once per output, each with a different GPU, in the same throttle window; a single
+/// shared sampler would let one output's sample get reused and mislabeled as another's.
+#[derive(Default)]
+pub struct TelemetryRegistry {
+    samplers: HashMap<Option<DrmNode>, TelemetrySampler>,
+}
+
+impl TelemetryRegistry {
+    pub fn sample(&mut self, gpu: Option<&DrmNode>) -> Telemetry {
+        self.samplers
+            .entry(gpu.copied())
+            .or_default()
+            .sample(gpu)
+            .clone()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Telemetry {
+    pub cpu_usage: Option<f32>,
+    pub cpu_temp_c: Option<f32>,
+    pub cpu_power_w: Option<f32>,
+    pub ram_used_mb: Option<f32>,
+    pub ram_total_mb: Option<f32>,
+    pub gpu_usage: Option<f32>,
+    pub gpu_temp_c: Option<f32>,
+    pub gpu_power_w: Option<f32>,
+}
+
+#[derive(Debug, Default)]
+struct CpuStatSample {
+    busy: u64,
+    total: u64,
+}
+
+struct TelemetrySampler {
+    last_sample: Option<Instant>,
+    last_cpu_stat: Option<CpuStatSample>,
+    cached: Telemetry,
+}
+
+impl Default for TelemetrySampler {
+    fn default() -> Self {
+        TelemetrySampler {
+            last_sample: None,
+            last_cpu_stat: None,
+            cached: Telemetry::default(),
+        }
+    }
+}
+
+impl TelemetrySampler {
+    /// Returns the cached telemetry, refreshing it from `/proc` and `/sys` if
+    /// more than [`SAMPLE_INTERVAL`] has passed since the last refresh.
+    fn sample(&mut self, gpu: Option<&DrmNode>) -> &Telemetry {
+        let now = Instant::now();
+        let due = self
+            .last_sample
+            .map(|last| now.duration_since(last) >= SAMPLE_INTERVAL)
+            .unwrap_or(true);
+
+        if due {
+            self.cached.cpu_usage = self.sample_cpu_usage();
+            let (temp, power) = sample_cpu_hwmon();
+            self.cached.cpu_temp_c = temp;
+            self.cached.cpu_power_w = power;
+
+            let (used, total) = sample_meminfo();
+            self.cached.ram_used_mb = used;
+            self.cached.ram_total_mb = total;
+
+            let (usage, temp, power) = gpu.map(sample_gpu).unwrap_or_default();
+            self.cached.gpu_usage = usage;
+            self.cached.gpu_temp_c = temp;
+            self.cached.gpu_power_w = power;
+
+            self.last_sample = Some(now);
+        }
+
+        &self.cached
+    }
+
+    fn sample_cpu_usage(&mut self) -> Option<f32> {
+        let contents = fs::read_to_string("/proc/stat").ok()?;
+        let line = contents.lines().find(|line| line.starts_with("cpu "))?;
+        let mut fields = line.split_whitespace().skip(1).filter_map(|f| f.parse::<u64>().ok());
+        let user = fields.next()?;
+        let nice = fields.next()?;
+        let system = fields.next()?;
+        let idle = fields.next()?;
+        let iowait = fields.next().unwrap_or(0);
+        let irq = fields.next().unwrap_or(0);
+        let softirq = fields.next().unwrap_or(0);
+        let steal = fields.next().unwrap_or(0);
+
+        let busy = user + nice + system + irq + softirq + steal;
+        let total = busy + idle + iowait;
+        let sample = CpuStatSample { busy, total };
+
+        let usage = self.last_cpu_stat.as_ref().and_then(|prev| {
+            let d_busy = sample.busy.saturating_sub(prev.busy);
+            let d_total = sample.total.saturating_sub(prev.total);
+            (d_total > 0).then(|| d_busy as f32 / d_total as f32 * 100.0)
+        });
+        self.last_cpu_stat = Some(sample);
+        usage
+    }
+}
+
+fn sample_meminfo() -> (Option<f32>, Option<f32>) {
+    let contents = match fs::read_to_string("/proc/meminfo") {
+        Ok(contents) => contents,
+        Err(_) => return (None, None),
+    };
+
+    let mut total_kb = None;
+    let mut available_kb = None;
+    for line in contents.lines() {
+        if let Some(rest) = line.strip_prefix("MemTotal:") {
+            total_kb = parse_kb_field(rest);
+        } else if let Some(rest) = line.strip_prefix("MemAvailable:") {
+            available_kb = parse_kb_field(rest);
+        }
+    }
+
+    match (total_kb, available_kb) {
+        (Some(total), Some(available)) => (
+            Some((total - available) as f32 / 1024.0),
+            Some(total as f32 / 1024.0),
+        ),
+        _ => (None, None),
+    }
+}
+
+fn parse_kb_field(rest: &str) -> Option<u64> {
+    rest.split_whitespace().next()?.parse().ok()
+}
+
+fn sample_cpu_hwmon() -> (Option<f32>, Option<f32>) {
+    let hwmon_dir = match fs::read_dir("/sys/class/hwmon") {
+        Ok(dir) => dir,
+        Err(_) => return (None, None),
+    };
+
+    for entry in hwmon_dir.filter_map(Result::ok) {
+        let path = entry.path();
+        let name = fs::read_to_string(path.join("name")).unwrap_or_default();
+        let name = name.trim();
+        if !matches!(name, "coretemp" | "k10temp" | "zenpower") {
+            continue;
+        }
+
+        let temp = read_sysfs_value(&path.join("temp1_input")).map(|v| v / 1000.0);
+        let power = read_sysfs_value(&path.join("power1_average")).map(|v| v / 1_000_000.0);
+        return (temp, power);
+    }
+
+    (None, None)
+}
+
+/// Reads GPU utilization/temperature/power for the given DRM render node.
+///
+/// Only amdgpu is implemented so far; other vendors fall back to `None` for
+/// every field until they grow their own sysfs readout below.
+fn sample_gpu(gpu: &DrmNode) -> (Option<f32>, Option<f32>, Option<f32>) {
+    let minor = gpu.minor();
+    let device_dir = std::path::PathBuf::from(format!(
+        "/sys/class/drm/renderD{}/device",
+        minor
+    ));
+
+    let vendor = fs::read_to_string(device_dir.join("vendor"))
+        .ok()
+        .and_then(|s| u32::from_str_radix(s.trim().trim_start_matches("0x"), 16).ok());
+
+    match vendor {
+        // AMD
+        Some(0x1002) => {
+            let usage = read_sysfs_value(&device_dir.join("gpu_busy_percent"));
+            let (temp, power) = sample_amdgpu_hwmon(&device_dir);
+            (usage, temp, power)
+        }
+        _ => (None, None, None),
+    }
+}
+
+fn sample_amdgpu_hwmon(device_dir: &std::path::Path) -> (Option<f32>, Option<f32>) {
+    let hwmon_root = device_dir.join("hwmon");
+    let entry = fs::read_dir(&hwmon_root)
+        .ok()
+        .and_then(|mut dir| dir.next())
+        .and_then(Result::ok);
+    let hwmon_dir = match entry {
+        Some(entry) => entry.path(),
+        None => return (None, None),
+    };
+
+    let temp = read_sysfs_value(&hwmon_dir.join("temp1_input")).map(|v| v / 1000.0);
+    let power = read_sysfs_value(&hwmon_dir.join("power1_average")).map(|v| v / 1_000_000.0);
+    (temp, power)
+}
+
+fn read_sysfs_value(path: &std::path::Path) -> Option<f32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}